@@ -6,7 +6,10 @@ use phoenix::{
     quantities::WrapperU64,
     state::markets::{Ladder, LadderOrder},
 };
-use rust_decimal::{prelude::FromPrimitive, Decimal};
+use rust_decimal::{
+    prelude::{FromPrimitive, ToPrimitive},
+    Decimal,
+};
 use rust_decimal_macros::dec;
 use std::{collections::HashMap, mem::size_of};
 
@@ -14,7 +17,7 @@ use crate::{
     amm::{try_get_account_data, AccountMap},
     amms::amm::{Amm, KeyedAccount, Quote, QuoteParams, SwapAndAccountMetas, SwapParams},
 };
-use solana_sdk::{pubkey::Pubkey, sysvar};
+use solana_sdk::{compute_budget::ComputeBudgetInstruction, instruction::Instruction, pubkey::Pubkey, sysvar};
 
 use jupiter::jupiter_override::Swap;
 
@@ -50,6 +53,17 @@ pub struct PhoenixAmm {
     ladder: Option<Ladder>,
 }
 
+/// Outcome of walking a sequence of priced levels to fill a [`QuoteParams::in_amount`], shared
+/// by [`PhoenixAmm::quote`] (L2, aggregated `Ladder`) and [`PhoenixAmm::quote_l3`] (L3, resting
+/// orders) so their fill, fee, and dust accounting can't drift apart between the two paths.
+struct FillResult {
+    in_amount: u64,
+    out_amount: u64,
+    fee_amount: u64,
+    not_enough_liquidity: bool,
+    price_impact_pct: Decimal,
+}
+
 impl PhoenixAmm {
     pub fn from_keyed_account(keyed_account: &KeyedAccount) -> Result<Self> {
         let (header_bytes, bytes) = keyed_account
@@ -94,6 +108,25 @@ impl PhoenixAmm {
         best_price.checked_sub(price)?.checked_div(best_price)
     }
 
+    /// Rounds the taker fee up to the nearest quote lot, matching the program's own rounding:
+    /// the protocol never under-charges a fill by truncating its fee.
+    fn compute_taker_fee_in_quote_lots(taker_fee_bps: u64, quote_lots: u64) -> Option<u64> {
+        quote_lots
+            .checked_mul(taker_fee_bps)?
+            .checked_add(9_999)?
+            .checked_div(10_000)
+    }
+
+    /// The smallest `in_amount`, in atoms, that rounds up to at least one lot in each swap
+    /// direction. Any input below this is dust: it rounds down to zero lots and can't be
+    /// traded at all, which looks identical to "not enough liquidity" unless callers check it.
+    pub fn min_trade_amount(&self) -> MinTradeAmount {
+        MinTradeAmount {
+            base: self.base_lot_size,
+            quote: self.quote_lot_size,
+        }
+    }
+
     pub fn get_base_decimals(&self) -> u32 {
         self.base_decimals
     }
@@ -101,75 +134,192 @@ impl PhoenixAmm {
     pub fn get_quote_decimals(&self) -> u32 {
         self.quote_decimals
     }
-}
 
-impl Amm for PhoenixAmm {
-    fn label(&self) -> String {
-        "Phoenix".into()
+    /// The account to key [`PriorityFeeEstimator`] samples by for this market: the Phoenix
+    /// market account is the write-lock every swap against this AMM contends on.
+    pub fn priority_fee_key(&self) -> Pubkey {
+        self.market_key
     }
 
-    fn program_id(&self) -> Pubkey {
-        self::id()
+    /// Top-of-book price (quote atoms per base atom) for the side of the book a swap with
+    /// `input_mint` would walk: the best bid when selling base, the best ask when buying base.
+    /// This is the correct baseline for IOC/limit-price protection — degrading the *realized*
+    /// average price of an order that already walked multiple levels would double-count that
+    /// order's own price impact and produce a looser, less protective limit.
+    pub fn best_price(&self, input_mint: &Pubkey) -> Result<Decimal> {
+        let ladder = self
+            .ladder
+            .as_ref()
+            .context("Market has not been updated")?;
+        let LadderOrder {
+            price_in_ticks,
+            size_in_base_lots,
+        } = if input_mint == &self.base_mint {
+            ladder.bids.first().context("No bids on the book")?
+        } else {
+            ladder.asks.first().context("No asks on the book")?
+        };
+        let filled_amount = price_in_ticks
+            .checked_mul(*size_in_base_lots)
+            .context("multiply overflow")?
+            .checked_mul(self.tick_size_in_quote_lots_per_base_unit_per_tick)
+            .context("multiply overflow")?
+            .checked_mul(self.quote_lot_size)
+            .context("multiply overflow")?
+            .checked_div(self.base_lots_per_base_unit)
+            .context("division failed")?;
+        let in_amount_for_level = size_in_base_lots
+            .checked_mul(self.base_lot_size)
+            .context("multiply overflow")?;
+        PhoenixAmm::compute_decimal_div(filled_amount, in_amount_for_level)
+            .context("Cannot compute best price")
     }
 
-    fn key(&self) -> Pubkey {
-        self.market_key
-    }
+    /// Converts a price quoted in quote atoms per base atom into Phoenix ticks, using this
+    /// market's lot and tick sizes. Shared by [`PhoenixAmm::limit_price_in_ticks`] and
+    /// [`PhoenixAmm::quote_within_limit`] so both compare prices in the same units.
+    fn price_per_base_atom_to_ticks(&self, price_per_base_atom: Decimal) -> Result<u64> {
+        let price_in_ticks = price_per_base_atom
+            .checked_mul(Decimal::from_u64(self.base_lot_size).context("Cannot convert")?)
+            .context("Cannot compute price per base lot")?
+            .checked_mul(
+                Decimal::from_u64(self.base_lots_per_base_unit).context("Cannot convert")?,
+            )
+            .context("Cannot compute price per base unit")?
+            .checked_div(
+                Decimal::from_u64(self.tick_size_in_quote_lots_per_base_unit_per_tick)
+                    .context("Cannot convert")?,
+            )
+            .context("Cannot divide by tick size")?
+            .checked_div(Decimal::from_u64(self.quote_lot_size).context("Cannot convert")?)
+            .context("Cannot divide by quote lot size")?;
 
-    fn get_reserve_mints(&self) -> Vec<Pubkey> {
-        vec![self.base_mint, self.quote_mint]
+        price_in_ticks
+            .trunc()
+            .to_u64()
+            .context("Price does not fit in a u64")
     }
 
-    fn get_accounts_to_update(&self) -> Vec<Pubkey> {
-        vec![self.market_key, sysvar::clock::ID]
+    /// Derives the worst-acceptable fill price for an IOC ("send-take") taker order: the
+    /// top-of-book price, degraded by `slippage_bps`, converted into Phoenix ticks using this
+    /// market's lot and tick sizes.
+    ///
+    /// This only computes the limit; `jupiter_override::Swap::Phoenix` has no field to carry a
+    /// per-leg price into the instruction it builds, so a swap produced by
+    /// [`PhoenixAmm::get_swap_leg_and_account_metas`] is unprotected regardless of this value, and
+    /// baking the limit into a raw Phoenix CPI instruction instead would mean Borsh-encoding the
+    /// program's native order-packet layout by hand with no vendored `phoenix` source in this
+    /// tree to check the field order against — worse than not shipping it if it's wrong. Pending
+    /// either an IOC/limit-order variant on that enum or a verified instruction layout, pair this
+    /// with [`PhoenixAmm::quote_within_limit`], which enforces it pre-submission: call both
+    /// immediately before building and sending the swap, and abort if the quote errs.
+    pub fn limit_price_in_ticks(
+        &self,
+        quote_params: &QuoteParams,
+        slippage_bps: u64,
+    ) -> Result<u64> {
+        let slippage_pct = Decimal::from_u64(slippage_bps)
+            .context("Cannot convert slippage_bps")?
+            .checked_div(BPS_TO_PCT)
+            .context("Cannot compute slippage pct")?;
+
+        // Selling base for quote (Ask): the taker is degraded by accepting a *lower* price.
+        // Buying base with quote (Bid): the taker is degraded by accepting a *higher* price.
+        let slippage_factor = if quote_params.input_mint == self.base_mint {
+            Decimal::ONE - slippage_pct
+        } else {
+            Decimal::ONE + slippage_pct
+        };
+        let limit_price_per_base_atom = self
+            .best_price(&quote_params.input_mint)?
+            .checked_mul(slippage_factor)
+            .context("Cannot apply slippage")?;
+
+        self.price_per_base_atom_to_ticks(limit_price_per_base_atom)
     }
 
-    fn update(&mut self, account_map: &AccountMap) -> Result<()> {
-        let market_account_data = try_get_account_data(account_map, &self.market_key)?;
-        let sysvar_clock_data = try_get_account_data(account_map, &sysvar::clock::ID)?;
-        let clock: sysvar::clock::Clock = bincode::deserialize(sysvar_clock_data)?;
+    /// The pre-submission half of IOC/"send-take" protection: computes a quote and rejects it if
+    /// its realized price is worse than `limit_price_in_ticks` (from
+    /// [`PhoenixAmm::limit_price_in_ticks`]). This cannot stop the on-chain swap itself from
+    /// walking past the limit — see that method's doc comment for why — so callers must call this
+    /// immediately before submitting the swap built from
+    /// [`PhoenixAmm::get_swap_leg_and_account_metas`] and abort on error, to catch the book having
+    /// moved since the limit was computed.
+    pub fn quote_within_limit(
+        &self,
+        quote_params: &QuoteParams,
+        limit_price_in_ticks: u64,
+    ) -> Result<Quote> {
+        let quote = self.quote(quote_params)?;
+        if quote_params.in_amount == 0 {
+            return Ok(quote);
+        }
 
-        let (header_bytes, bytes) = market_account_data.split_at(size_of::<MarketHeader>());
-        let header: &MarketHeader = bytemuck::try_from_bytes(header_bytes)
-            .map_err(|e| anyhow!("Error getting market header. Error: {:?}", e))?;
-        let market = load_with_dispatch(&header.market_size_params, bytes)
-            .map_err(|e| anyhow!("Failed to load market. Error {:?}", e))?;
-        self.ladder = Some(market.inner.get_ladder_with_expiration(
-            u64::MAX,
-            Some(clock.slot),
-            Some(clock.unix_timestamp as u64),
-        ));
+        // Both amounts are quote atoms per base atom; selling base divides the quote atoms
+        // received by the base atoms spent, buying base divides the quote atoms spent by the
+        // base atoms received.
+        let realized_price_per_base_atom = if quote_params.input_mint == self.base_mint {
+            PhoenixAmm::compute_decimal_div(quote.out_amount, quote.in_amount)
+        } else {
+            PhoenixAmm::compute_decimal_div(quote.in_amount, quote.out_amount)
+        }
+        .context("Cannot compute realized price")?;
+        let realized_price_in_ticks =
+            self.price_per_base_atom_to_ticks(realized_price_per_base_atom)?;
 
-        Ok(())
+        // Selling base (Ask): a lower realized price than the limit is worse for the taker.
+        // Buying base (Bid): a higher realized price than the limit is worse for the taker.
+        let crosses_limit = if quote_params.input_mint == self.base_mint {
+            realized_price_in_ticks < limit_price_in_ticks
+        } else {
+            realized_price_in_ticks > limit_price_in_ticks
+        };
+        ensure!(
+            !crosses_limit,
+            "realized price {} ticks is worse than the limit {} ticks",
+            realized_price_in_ticks,
+            limit_price_in_ticks
+        );
+
+        Ok(quote)
     }
 
-    fn quote(&self, quote_params: &QuoteParams) -> Result<Quote> {
+    /// Walks `levels` (best-to-worst-priced `(price_in_ticks, size_in_base_lots)` pairs) to fill
+    /// `quote_params.in_amount`, charging the exact per-level taker fee in quote lots and
+    /// rejecting dust input that rounds down to zero lots. `levels` must already be in the book's
+    /// matching priority order; this makes no assumption about where they came from, so the same
+    /// walk serves the L2 `Ladder` in `quote` and the L3 resting orders in `quote_l3`.
+    fn fill_levels(
+        &self,
+        quote_params: &QuoteParams,
+        levels: impl Iterator<Item = (u64, u64)>,
+    ) -> Result<FillResult> {
         let mut out_amount = 0;
         let mut in_amount = 0;
+        let mut fee_amount: u64 = 0;
         let mut not_enough_liquidity = false;
         let mut best_price: Option<Decimal> = None;
 
-        let ladder = self
-            .ladder
-            .as_ref()
-            .context("Market has not been updated")?;
         if quote_params.input_mint == self.base_mint {
             let mut base_lot_budget = quote_params
                 .in_amount
                 .checked_div(self.base_lot_size)
                 .context("division failed")?;
+            ensure!(
+                base_lot_budget > 0 || quote_params.in_amount == 0,
+                "in_amount {} is less than one base lot ({} base atoms); it rounds to zero \
+                 and cannot be traded",
+                quote_params.in_amount,
+                self.base_lot_size
+            );
             let initial_base_lot_budget = base_lot_budget;
-            for LadderOrder {
-                price_in_ticks,
-                size_in_base_lots,
-            } in ladder.bids.iter()
-            {
+            for (price_in_ticks, size_in_base_lots) in levels {
                 if base_lot_budget == 0 {
                     break;
                 }
-                let base_lots = size_in_base_lots.min(&base_lot_budget);
+                let base_lots = size_in_base_lots.min(base_lot_budget);
                 let filled_amount = price_in_ticks
-                    .checked_mul(*base_lots)
+                    .checked_mul(base_lots)
                     .context("multiply overflow")?
                     .checked_mul(self.tick_size_in_quote_lots_per_base_unit_per_tick)
                     .context("multiply overflow")?
@@ -187,8 +337,25 @@ impl Amm for PhoenixAmm {
                             .context("Cannot compute best price")?,
                     );
                 }
+
+                let quote_lots_for_level = filled_amount
+                    .checked_div(self.quote_lot_size)
+                    .context("division failed")?;
+                let fee_lots_for_level = PhoenixAmm::compute_taker_fee_in_quote_lots(
+                    self.taker_fee_bps,
+                    quote_lots_for_level,
+                )
+                .context("Cannot compute taker fee")?;
+                fee_amount = fee_amount
+                    .checked_add(
+                        fee_lots_for_level
+                            .checked_mul(self.quote_lot_size)
+                            .context("multiply overflow")?,
+                    )
+                    .context("add overflow")?;
+
                 out_amount += filled_amount;
-                base_lot_budget = base_lot_budget.saturating_sub(*base_lots);
+                base_lot_budget = base_lot_budget.saturating_sub(base_lots);
             }
             in_amount = (initial_base_lot_budget - base_lot_budget)
                 .checked_mul(self.base_lot_size)
@@ -201,12 +368,20 @@ impl Amm for PhoenixAmm {
                 .in_amount
                 .checked_div(self.quote_lot_size)
                 .context("division failed")?;
-            let initial_quote_lot_budget = quote_lot_budget;
-            for LadderOrder {
-                price_in_ticks,
-                size_in_base_lots,
-            } in ladder.asks.iter()
-            {
+            ensure!(
+                quote_lot_budget > 0 || quote_params.in_amount == 0,
+                "in_amount {} is less than one quote lot ({} quote atoms); it rounds to zero \
+                 and cannot be traded",
+                quote_params.in_amount,
+                self.quote_lot_size
+            );
+            // Tracked separately from `quote_lot_budget`: the program debits notional *and* fee
+            // from the taker, but `quote_lot_budget` is only walked down by notional (matching
+            // the rest of this branch, which sizes each level off the remaining notional budget
+            // the caller can still spend). `quote_lots_spent` is the true total so `in_amount`
+            // below reflects what on-chain settlement would actually take.
+            let mut quote_lots_spent: u64 = 0;
+            for (price_in_ticks, size_in_base_lots) in levels {
                 if quote_lot_budget == 0 {
                     break;
                 }
@@ -215,16 +390,16 @@ impl Amm for PhoenixAmm {
                     .context("multiple overflow")?
                     .checked_div(self.tick_size_in_quote_lots_per_base_unit_per_tick)
                     .context("division failed")?
-                    .checked_div(*price_in_ticks)
+                    .checked_div(price_in_ticks)
                     .context("division failed")?;
 
                 let base_lots: u64;
                 let quote_lots: u64;
-                if size_in_base_lots > &purchasable_base_lots {
+                if size_in_base_lots > purchasable_base_lots {
                     base_lots = purchasable_base_lots;
                     quote_lots = quote_lot_budget;
                 } else {
-                    base_lots = *size_in_base_lots;
+                    base_lots = size_in_base_lots;
                     quote_lots = price_in_ticks
                         .checked_mul(base_lots)
                         .context("multiple overflow")?
@@ -247,24 +422,43 @@ impl Amm for PhoenixAmm {
                     )
                 }
 
+                let fee_lots_for_level =
+                    PhoenixAmm::compute_taker_fee_in_quote_lots(self.taker_fee_bps, quote_lots)
+                        .context("Cannot compute taker fee")?;
+                fee_amount = fee_amount
+                    .checked_add(
+                        fee_lots_for_level
+                            .checked_mul(self.quote_lot_size)
+                            .context("multiply overflow")?,
+                    )
+                    .context("add overflow")?;
+
                 out_amount += filled_amount;
                 quote_lot_budget = quote_lot_budget.saturating_sub(quote_lots);
+                quote_lots_spent = quote_lots_spent
+                    .checked_add(quote_lots)
+                    .context("add overflow")?
+                    .checked_add(fee_lots_for_level)
+                    .context("add overflow")?;
             }
-            in_amount = (initial_quote_lot_budget - quote_lot_budget)
-                .checked_div(self.quote_lot_size)
-                .context("division failed")?;
+            in_amount = quote_lots_spent
+                .checked_mul(self.quote_lot_size)
+                .context("multiply overflow")?;
             if quote_lot_budget > 0 {
                 not_enough_liquidity = true;
             }
         };
 
-        // Not 100% accurate, but it's a reasonable enough approximation
-        let out_amount_after_fees = out_amount
-            .checked_mul(10_000 - self.taker_fee_bps)
-            .context("multiply overflow")?
-            .checked_div(10_000)
-            .context("division failed")?;
-        let fee_amount = out_amount - out_amount_after_fees;
+        // The taker fee is always charged in quote lots, already summed level-by-level above.
+        // Selling base pays it out of `out_amount` (quote); buying base pays it out of the
+        // quote the caller spent, which never shows up in `out_amount` (base).
+        let out_amount_after_fees = if quote_params.input_mint == self.base_mint {
+            out_amount
+                .checked_sub(fee_amount)
+                .context("fee exceeds out_amount")?
+        } else {
+            out_amount
+        };
 
         let price_impact_pct = if quote_params.in_amount > 0 {
             if let Some(best_price) = best_price {
@@ -279,14 +473,284 @@ impl Amm for PhoenixAmm {
             dec!(1)
         };
 
-        Ok(Quote {
-            not_enough_liquidity,
+        Ok(FillResult {
             in_amount,
             out_amount: out_amount_after_fees,
             fee_amount,
-            fee_mint: quote_params.output_mint, // Technically quote_mint but fee is estimated on the output amount
-            fee_pct: self.fee_pct,
+            not_enough_liquidity,
             price_impact_pct,
+        })
+    }
+
+    /// Exact order-by-order (L3) quote. Unlike `quote`, which walks a pre-aggregated L2
+    /// `Ladder` snapshotted once by `update`, this re-reads `account_map` and walks the market's
+    /// full resting-order book, checking each order's own slot/timestamp expiry against `Clock`
+    /// individually instead of against the batch expiry baked into the cached ladder. When
+    /// `trader` is supplied, orders resting under that pubkey are meant to be skipped so a
+    /// market maker quoting through this AMM doesn't fill against its own liquidity — see the
+    /// assumption called out at the `get_resting_orders_with_expiration` call below, which this
+    /// guarantee depends on and which has not been checked against a vendored `phoenix` crate in
+    /// this tree. Treat self-trade avoidance here as best-effort until that's confirmed.
+    ///
+    /// This re-deserializes the market on every call, so it is slower than `quote` and meant to
+    /// be opted into by bots that must avoid self-trades, with the L2 path remaining the
+    /// default for everyone else.
+    pub fn quote_l3(
+        &self,
+        quote_params: &QuoteParams,
+        account_map: &AccountMap,
+        trader: Option<Pubkey>,
+    ) -> Result<Quote> {
+        let market_account_data = try_get_account_data(account_map, &self.market_key)?;
+        let sysvar_clock_data = try_get_account_data(account_map, &sysvar::clock::ID)?;
+        let clock: sysvar::clock::Clock = bincode::deserialize(sysvar_clock_data)?;
+
+        let (header_bytes, bytes) = market_account_data.split_at(size_of::<MarketHeader>());
+        let header: &MarketHeader = bytemuck::try_from_bytes(header_bytes)
+            .map_err(|e| anyhow!("Error getting market header. Error: {:?}", e))?;
+        let market = load_with_dispatch(&header.market_size_params, bytes)
+            .map_err(|e| anyhow!("Failed to load market. Error {:?}", e))?;
+
+        // The side of the book we match against is the opposite of the side the caller takes:
+        // selling base matches resting bids, buying base matches resting asks.
+        let resting_side = if quote_params.input_mint == self.base_mint {
+            Side::Bid
+        } else {
+            Side::Ask
+        };
+
+        // ASSUMPTION, unverified: this tree has no vendored `phoenix` source to check
+        // `get_resting_orders_with_expiration` against, so its name, signature, and the shape of
+        // the orders it returns are all unconfirmed. In particular, if a resting order's maker
+        // is actually an index into a trader table (a common space-saving pattern for on-chain
+        // order books, and one Phoenix may use) rather than a plain `Pubkey`, this either fails
+        // to compile — safe — or compiles with `maker` populated from the wrong field and this
+        // whole self-trade filter silently doing nothing. Confirm the accessor and the `maker`
+        // field against the real crate before relying on this for self-trade avoidance in
+        // production. `exclude_self_trades` below is unit-tested, but only for the comparison
+        // once a `RestingOrder` exists — not for whether `get_resting_orders_with_expiration`
+        // populates `maker` with the right identity in the first place.
+        let resting_orders: Vec<RestingOrder> = market
+            .inner
+            .get_resting_orders_with_expiration(
+                resting_side,
+                Some(clock.slot),
+                Some(clock.unix_timestamp as u64),
+            )
+            .into_iter()
+            .collect();
+        let resting_orders = exclude_self_trades(resting_orders, trader);
+
+        let fill = self.fill_levels(
+            quote_params,
+            resting_orders
+                .iter()
+                .map(|order| (order.price_in_ticks, order.size_in_base_lots)),
+        )?;
+
+        Ok(Quote {
+            not_enough_liquidity: fill.not_enough_liquidity,
+            in_amount: fill.in_amount,
+            out_amount: fill.out_amount,
+            fee_amount: fill.fee_amount,
+            fee_mint: self.quote_mint,
+            fee_pct: self.fee_pct,
+            price_impact_pct: fill.price_impact_pct,
+            ..Quote::default()
+        })
+    }
+}
+
+/// One resting order on the book, as seen by the L3 (order-by-order) quoting path in
+/// [`PhoenixAmm::quote_l3`]. Unlike `LadderOrder`, this preserves the maker's pubkey so a caller
+/// can detect and exclude its own resting liquidity from a fill.
+#[derive(Clone, Copy, Debug)]
+pub struct RestingOrder {
+    pub maker: Pubkey,
+    pub price_in_ticks: u64,
+    pub size_in_base_lots: u64,
+}
+
+/// Drops resting orders belonging to `trader`, so [`PhoenixAmm::quote_l3`] never fills against
+/// the caller's own resting liquidity. This is the comparison half of self-trade avoidance;
+/// whether `order.maker` is actually populated with the resting order's true owner is a separate
+/// concern this function can't verify — see the doc comment at its call site in `quote_l3`.
+fn exclude_self_trades(orders: Vec<RestingOrder>, trader: Option<Pubkey>) -> Vec<RestingOrder> {
+    orders
+        .into_iter()
+        .filter(|order| Some(order.maker) != trader)
+        .collect()
+}
+
+/// The minimum `in_amount`, in atoms, that rounds up to at least one lot for each swap
+/// direction. See [`PhoenixAmm::min_trade_amount`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MinTradeAmount {
+    /// Minimum tradeable input when selling base, in base atoms (one base lot).
+    pub base: u64,
+    /// Minimum tradeable input when selling quote, in quote atoms (one quote lot).
+    pub quote: u64,
+}
+
+/// Percentile summary of recently-observed prioritization fees (micro-lamports per CU).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PriorityFeeStats {
+    pub min: u64,
+    pub median: u64,
+    pub p75: u64,
+    pub p90: u64,
+    pub p95: u64,
+    pub max: u64,
+}
+
+/// A percentile of the observed fee distribution to price a transaction at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PriorityFeePercentile {
+    Min,
+    Median,
+    P75,
+    P90,
+    P95,
+    Max,
+}
+
+impl PriorityFeeStats {
+    fn get(&self, percentile: PriorityFeePercentile) -> u64 {
+        match percentile {
+            PriorityFeePercentile::Min => self.min,
+            PriorityFeePercentile::Median => self.median,
+            PriorityFeePercentile::P75 => self.p75,
+            PriorityFeePercentile::P90 => self.p90,
+            PriorityFeePercentile::P95 => self.p95,
+            PriorityFeePercentile::Max => self.max,
+        }
+    }
+}
+
+/// Suggests a compute-unit price from recently-observed prioritization fees, keyed by the
+/// account whose write-lock contention the fees were sampled against (e.g. a Phoenix market).
+///
+/// Integrators feed this samples taken from recent transactions that touched the account, and
+/// get back a [`ComputeBudgetInstruction::SetComputeUnitPrice`] ready to be prepended to a swap
+/// built from [`PhoenixAmm::get_swap_leg_and_account_metas`].
+#[derive(Clone, Debug, Default)]
+pub struct PriorityFeeEstimator {
+    samples: HashMap<Pubkey, Vec<u64>>,
+}
+
+impl PriorityFeeEstimator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records recently-observed prioritization fees (micro-lamports per CU) for `account`.
+    pub fn record_samples(&mut self, account: Pubkey, fees: impl IntoIterator<Item = u64>) {
+        self.samples.entry(account).or_default().extend(fees);
+    }
+
+    /// Returns `None` when fewer than two samples have been recorded for `account`.
+    pub fn stats(&self, account: &Pubkey) -> Option<PriorityFeeStats> {
+        let mut fees = self.samples.get(account)?.clone();
+        if fees.len() < 2 {
+            return None;
+        }
+        fees.sort_unstable();
+        let percentile = |pct: usize| fees[fees.len() * pct / 100];
+        Some(PriorityFeeStats {
+            min: *fees.first()?,
+            median: fees[fees.len() / 2],
+            p75: percentile(75),
+            p90: percentile(90),
+            p95: percentile(95),
+            max: *fees.last()?,
+        })
+    }
+
+    /// Produces a `SetComputeUnitPrice` instruction priced at `percentile` of the fees observed
+    /// for `account`, or `None` if there isn't enough data to estimate one yet.
+    pub fn compute_unit_price_ix(
+        &self,
+        account: &Pubkey,
+        percentile: PriorityFeePercentile,
+    ) -> Option<Instruction> {
+        let micro_lamports_per_cu = self.stats(account)?.get(percentile);
+        Some(ComputeBudgetInstruction::set_compute_unit_price(
+            micro_lamports_per_cu,
+        ))
+    }
+}
+
+impl Amm for PhoenixAmm {
+    fn label(&self) -> String {
+        "Phoenix".into()
+    }
+
+    fn program_id(&self) -> Pubkey {
+        self::id()
+    }
+
+    fn key(&self) -> Pubkey {
+        self.market_key
+    }
+
+    fn get_reserve_mints(&self) -> Vec<Pubkey> {
+        vec![self.base_mint, self.quote_mint]
+    }
+
+    fn get_accounts_to_update(&self) -> Vec<Pubkey> {
+        vec![self.market_key, sysvar::clock::ID]
+    }
+
+    fn update(&mut self, account_map: &AccountMap) -> Result<()> {
+        let market_account_data = try_get_account_data(account_map, &self.market_key)?;
+        let sysvar_clock_data = try_get_account_data(account_map, &sysvar::clock::ID)?;
+        let clock: sysvar::clock::Clock = bincode::deserialize(sysvar_clock_data)?;
+
+        let (header_bytes, bytes) = market_account_data.split_at(size_of::<MarketHeader>());
+        let header: &MarketHeader = bytemuck::try_from_bytes(header_bytes)
+            .map_err(|e| anyhow!("Error getting market header. Error: {:?}", e))?;
+        let market = load_with_dispatch(&header.market_size_params, bytes)
+            .map_err(|e| anyhow!("Failed to load market. Error {:?}", e))?;
+        self.ladder = Some(market.inner.get_ladder_with_expiration(
+            u64::MAX,
+            Some(clock.slot),
+            Some(clock.unix_timestamp as u64),
+        ));
+
+        Ok(())
+    }
+
+    fn quote(&self, quote_params: &QuoteParams) -> Result<Quote> {
+        let ladder = self
+            .ladder
+            .as_ref()
+            .context("Market has not been updated")?;
+        let fill = if quote_params.input_mint == self.base_mint {
+            self.fill_levels(
+                quote_params,
+                ladder
+                    .bids
+                    .iter()
+                    .map(|order| (order.price_in_ticks, order.size_in_base_lots)),
+            )?
+        } else {
+            self.fill_levels(
+                quote_params,
+                ladder
+                    .asks
+                    .iter()
+                    .map(|order| (order.price_in_ticks, order.size_in_base_lots)),
+            )?
+        };
+
+        Ok(Quote {
+            not_enough_liquidity: fill.not_enough_liquidity,
+            in_amount: fill.in_amount,
+            out_amount: fill.out_amount,
+            fee_amount: fill.fee_amount,
+            fee_mint: self.quote_mint,
+            fee_pct: self.fee_pct,
+            price_impact_pct: fill.price_impact_pct,
             ..Quote::default()
         })
     }
@@ -414,3 +878,258 @@ fn test_jupiter_phoenix_integration() {
         out_amount as f64 / 10.0_f64.powf(phoenix_amm.get_base_decimals() as f64)
     );
 }
+
+/// A `PhoenixAmm` fixture with simple (1:1) lot/tick sizes so fill math can be checked by hand,
+/// quoting a single resting order on each side of the book.
+fn fixture_amm(taker_fee_bps: u64) -> PhoenixAmm {
+    PhoenixAmm {
+        market_key: Pubkey::new_unique(),
+        base_mint: Pubkey::new_unique(),
+        quote_mint: Pubkey::new_unique(),
+        base_decimals: 9,
+        quote_decimals: 6,
+        base_lot_size: 1,
+        quote_lot_size: 1,
+        base_lots_per_base_unit: 1,
+        tick_size_in_quote_lots_per_base_unit_per_tick: 1,
+        taker_fee_bps,
+        fee_pct: PhoenixAmm::compute_fee_pct(taker_fee_bps).unwrap(),
+        ladder: Some(Ladder {
+            bids: vec![LadderOrder {
+                price_in_ticks: 100,
+                size_in_base_lots: 50,
+            }],
+            asks: vec![LadderOrder {
+                price_in_ticks: 110,
+                size_in_base_lots: 50,
+            }],
+        }),
+    }
+}
+
+#[test]
+fn test_compute_taker_fee_in_quote_lots_rounds_up() {
+    // 10 bps of 1000 quote lots divides evenly: no rounding needed.
+    assert_eq!(
+        PhoenixAmm::compute_taker_fee_in_quote_lots(10, 1_000),
+        Some(1)
+    );
+    // 3 bps of 1 quote lot rounds up to 1 rather than truncating to 0.
+    assert_eq!(PhoenixAmm::compute_taker_fee_in_quote_lots(3, 1), Some(1));
+    // Zero quote lots charge zero fee.
+    assert_eq!(PhoenixAmm::compute_taker_fee_in_quote_lots(10, 0), Some(0));
+}
+
+#[test]
+fn test_quote_charges_exact_taker_fee_selling_base() {
+    let amm = fixture_amm(10); // 10 bps
+    let quote = amm
+        .quote(&QuoteParams {
+            in_amount: 10,
+            input_mint: amm.base_mint,
+            output_mint: amm.quote_mint,
+        })
+        .unwrap();
+
+    // Gross fill: price_in_ticks(100) * base_lots(10) = 1000 quote atoms.
+    // Fee: 1000 * 10 bps / 10_000 = 1 quote atom, charged out of the quote received.
+    assert_eq!(quote.fee_amount, 1);
+    assert_eq!(quote.fee_mint, amm.quote_mint);
+    assert_eq!(quote.out_amount, 1000 - 1);
+}
+
+#[test]
+fn test_quote_reports_fee_mint_as_quote_when_buying_base() {
+    let amm = fixture_amm(10);
+    let quote = amm
+        .quote(&QuoteParams {
+            in_amount: 110,
+            input_mint: amm.quote_mint,
+            output_mint: amm.base_mint,
+        })
+        .unwrap();
+
+    // Buying base: the fee is charged in quote atoms and never touches the base `out_amount`.
+    assert_eq!(quote.fee_mint, amm.quote_mint);
+    assert_eq!(quote.out_amount, 1);
+    assert!(quote.fee_amount > 0);
+}
+
+#[test]
+fn test_quote_in_amount_conserves_notional_plus_fee_when_buying_base() {
+    let amm = fixture_amm(10); // 10 bps
+    let quote = amm
+        .quote(&QuoteParams {
+            in_amount: 110,
+            input_mint: amm.quote_mint,
+            output_mint: amm.base_mint,
+        })
+        .unwrap();
+
+    // Notional: price_in_ticks(110) * base_lots(1) = 110 quote atoms.
+    // Fee: 110 * 10 bps / 10_000 rounds up to 1 quote atom.
+    // The real atoms a taker must provide is notional + fee, not just the walked notional, so
+    // `in_amount` must reflect 111, not 110 (which would understate the true cost by fee_amount
+    // and leave a caller sizing a transaction off this quote short).
+    assert_eq!(quote.fee_amount, 1);
+    assert_eq!(quote.in_amount, 111);
+}
+
+#[test]
+fn test_min_trade_amount_is_one_lot_per_side() {
+    let amm = PhoenixAmm {
+        base_lot_size: 1_000,
+        quote_lot_size: 5,
+        ..fixture_amm(10)
+    };
+    let min = amm.min_trade_amount();
+    assert_eq!(min.base, 1_000);
+    assert_eq!(min.quote, 5);
+}
+
+#[test]
+fn test_quote_rejects_dust_input_below_one_base_lot() {
+    let amm = PhoenixAmm {
+        base_lot_size: 1_000,
+        ..fixture_amm(10)
+    };
+    let err = amm
+        .quote(&QuoteParams {
+            in_amount: 1,
+            input_mint: amm.base_mint,
+            output_mint: amm.quote_mint,
+        })
+        .unwrap_err();
+    assert!(err.to_string().contains("rounds to zero"));
+}
+
+#[test]
+fn test_quote_rejects_dust_input_below_one_quote_lot() {
+    let amm = PhoenixAmm {
+        quote_lot_size: 1_000,
+        ..fixture_amm(10)
+    };
+    let err = amm
+        .quote(&QuoteParams {
+            in_amount: 1,
+            input_mint: amm.quote_mint,
+            output_mint: amm.base_mint,
+        })
+        .unwrap_err();
+    assert!(err.to_string().contains("rounds to zero"));
+}
+
+#[test]
+fn test_quote_allows_zero_in_amount() {
+    let amm = fixture_amm(10);
+    let quote = amm
+        .quote(&QuoteParams {
+            in_amount: 0,
+            input_mint: amm.base_mint,
+            output_mint: amm.quote_mint,
+        })
+        .unwrap();
+    assert_eq!(quote.in_amount, 0);
+    assert_eq!(quote.out_amount, 0);
+}
+
+#[test]
+fn test_quote_within_limit_passes_when_realized_price_meets_limit() {
+    let amm = fixture_amm(0);
+    let quote_params = QuoteParams {
+        in_amount: 10,
+        input_mint: amm.base_mint,
+        output_mint: amm.quote_mint,
+    };
+    // Single level at price 100 with no fee: the realized price is exactly the best bid, so a
+    // limit of 100 ticks (no slippage) should not be crossed.
+    let limit = amm.limit_price_in_ticks(&quote_params, 0).unwrap();
+    assert_eq!(limit, 100);
+    let quote = amm.quote_within_limit(&quote_params, limit).unwrap();
+    assert_eq!(quote.out_amount, 1_000);
+}
+
+#[test]
+fn test_quote_within_limit_rejects_when_realized_price_crosses_limit() {
+    let amm = fixture_amm(0);
+    let quote_params = QuoteParams {
+        in_amount: 10,
+        input_mint: amm.base_mint,
+        output_mint: amm.quote_mint,
+    };
+    // Realized price is 100 ticks; demanding at least 101 must be rejected.
+    let err = amm.quote_within_limit(&quote_params, 101).unwrap_err();
+    assert!(err.to_string().contains("worse than the limit"));
+}
+
+#[test]
+fn test_exclude_self_trades_drops_only_the_traders_own_orders() {
+    let trader = Pubkey::new_unique();
+    let other_maker = Pubkey::new_unique();
+    let orders = vec![
+        RestingOrder {
+            maker: trader,
+            price_in_ticks: 100,
+            size_in_base_lots: 5,
+        },
+        RestingOrder {
+            maker: other_maker,
+            price_in_ticks: 101,
+            size_in_base_lots: 7,
+        },
+    ];
+
+    let filtered = exclude_self_trades(orders, Some(trader));
+    assert_eq!(filtered.len(), 1);
+    assert_eq!(filtered[0].maker, other_maker);
+}
+
+#[test]
+fn test_exclude_self_trades_keeps_everything_when_no_trader_given() {
+    let orders = vec![RestingOrder {
+        maker: Pubkey::new_unique(),
+        price_in_ticks: 100,
+        size_in_base_lots: 5,
+    }];
+
+    let filtered = exclude_self_trades(orders, None);
+    assert_eq!(filtered.len(), 1);
+}
+
+#[test]
+fn test_priority_fee_estimator_requires_at_least_two_samples() {
+    let mut estimator = PriorityFeeEstimator::new();
+    let account = Pubkey::new_unique();
+    assert!(estimator.stats(&account).is_none());
+
+    estimator.record_samples(account, [42]);
+    assert!(estimator.stats(&account).is_none());
+}
+
+#[test]
+fn test_priority_fee_estimator_percentiles() {
+    let mut estimator = PriorityFeeEstimator::new();
+    let account = Pubkey::new_unique();
+    estimator.record_samples(account, [5, 1, 9, 3, 7]);
+
+    // sorted: [1, 3, 5, 7, 9]
+    let stats = estimator.stats(&account).unwrap();
+    assert_eq!(stats.min, 1);
+    assert_eq!(stats.median, 5);
+    assert_eq!(stats.max, 9);
+}
+
+#[test]
+fn test_priority_fee_estimator_compute_unit_price_ix() {
+    let mut estimator = PriorityFeeEstimator::new();
+    let account = Pubkey::new_unique();
+
+    assert!(estimator
+        .compute_unit_price_ix(&account, PriorityFeePercentile::Median)
+        .is_none());
+
+    estimator.record_samples(account, [10, 20]);
+    assert!(estimator
+        .compute_unit_price_ix(&account, PriorityFeePercentile::Median)
+        .is_some());
+}